@@ -1,6 +1,8 @@
 //! This example uses the embedded-graphics library to draw text and an image on
 //! an external display. The external display is connected through the DSI link.
-//! DSI Video mode is used, so the display is constantly refreshed by hardware.
+//! DSI adapted command mode is used, so a frame is only pushed to the panel
+//! when explicitly requested, synchronized to its tearing-effect (TE) output
+//! via `EXTI2`.
 //!
 //! Run command: cargo embed --release --features="stm32h747cm7,dsi,log,ltdc,fmc,example-smps,log-rtt,rt,rtc" --example display-dsi-video-stm32h747i-disco
 //!
@@ -12,24 +14,30 @@
 #![no_main]
 #![no_std]
 
+use core::cell::RefCell;
 use core::{mem, slice};
 
 #[macro_use]
 mod utilities;
 mod utilities_display;
 
+use cortex_m::interrupt::Mutex;
 use log::info;
 use otm8009a::Otm8009AConfig;
-use stm32h7xx_hal::dsi::{ColorCoding, DsiChannel, DsiConfig, DsiPllConfig};
+use stm32h7xx_hal::dsi::{
+    ColorCoding, DsiChannel, DsiConfig, DsiPllConfig, DsiRefreshHandle,
+};
 
 extern crate cortex_m;
 extern crate cortex_m_rt as rt;
 use cortex_m_rt::{entry, exception};
 
 use crate::utilities_display::display_target::BufferedDisplay;
-use stm32h7xx_hal::gpio::Speed;
+use stm32h7xx_hal::gpio::gpioj::PJ2;
+use stm32h7xx_hal::gpio::{Edge, ExtiPin, Input, Speed};
 use stm32h7xx_hal::ltdc;
 use stm32h7xx_hal::stm32::rcc::d1ccipr::FMCSEL_A;
+use stm32h7xx_hal::stm32::interrupt;
 use stm32h7xx_hal::{prelude::*, rtc, stm32};
 
 use embedded_display_controller::DisplayController;
@@ -44,7 +52,7 @@ use embedded_display_controller::DisplayConfiguration;
 use otm8009a::Otm8009A;
 use stm32h7xx_hal::dsi::{
     DsiCmdModeTransmissionKind, DsiHost, DsiInterrupts, DsiMode, DsiPhyTimers,
-    DsiVideoMode, LaneCount,
+    LaneCount,
 };
 
 pub const WIDTH: usize = 800;
@@ -65,6 +73,15 @@ pub const DISPLAY_CONFIGURATION: DisplayConfiguration = DisplayConfiguration {
     pixel_clock_pol: false,
 };
 
+/// The panel's TE (tearing-effect) pin, shared between `main` and the
+/// `EXTI2` interrupt handler.
+static TE_PIN: Mutex<RefCell<Option<PJ2<Input>>>> =
+    Mutex::new(RefCell::new(None));
+/// The command-mode refresh handle armed by `main` and kicked on every TE
+/// rising edge, also shared with the `EXTI2` interrupt handler.
+static REFRESH_HANDLE: Mutex<RefCell<Option<DsiRefreshHandle>>> =
+    Mutex::new(RefCell::new(None));
+
 /// Configure a pin for the FMC controller
 macro_rules! fmc_pins {
     ($($pin:expr),*) => {
@@ -154,8 +171,8 @@ fn main() -> ! {
     let gpioi = dp.GPIOI.split(ccdr.peripheral.GPIOI);
     let gpioj = dp.GPIOJ.split(ccdr.peripheral.GPIOJ);
 
-    let _syscfg = dp.SYSCFG;
-    let _exti = dp.EXTI;
+    let mut syscfg = dp.SYSCFG;
+    let mut exti = dp.EXTI;
 
     // MPU config for SDRAM write-through
     let sdram_size = 32 * 1024 * 1024;
@@ -229,27 +246,38 @@ fn main() -> ! {
     let mut display_backlight_en = gpioj.pj12.into_push_pull_output();
     display_backlight_en.set_high();
 
-    // Display controller TE (hw tear effect sync) pin as input
-    // let _display_te = gpioj.pj2.into_alternate::<13>();
-    let _display_te = gpioj.pj2.into_input();
-    // display_te.make_interrupt_source(&mut syscfg);
-    // display_te.trigger_on_edge(&mut exti, Edge::Rising);
-    // display_te.enable_interrupt(&mut exti);
+    // Display controller TE (hw tear effect sync) pin as input, armed to
+    // fire EXTI2 on every rising edge.
+    let mut display_te = gpioj.pj2.into_input();
+    display_te.make_interrupt_source(&mut syscfg);
+    display_te.trigger_on_edge(&mut exti, Edge::Rising);
+    display_te.enable_interrupt(&mut exti);
+    cortex_m::interrupt::free(|cs| {
+        TE_PIN.borrow(cs).replace(Some(display_te));
+    });
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(stm32::Interrupt::EXTI2);
+    }
 
     let mut ltdc = ltdc::Ltdc::new(dp.LTDC, ccdr.peripheral.LTDC, &ccdr.clocks);
     ltdc.init(DISPLAY_CONFIGURATION);
+    // The pixel clock above is high enough to risk FIFO underruns, which
+    // show up as the diagonal tearing/flicker noted below in landscape mode.
+    ltdc.raise_fifo_prefetch_threshold();
+    ltdc.enable_interrupt(ltdc::LtdcInterrupt::FifoUnderrun);
 
-    let layer = ltdc.split();
+    let mut layer = ltdc.split();
+    layer.init(DISPLAY_CONFIGURATION, stm32h7xx_hal::dma2d::PixelFormat::Argb8888);
     let mut disp = BufferedDisplay::new(layer, fb1, fb2, WIDTH, HEIGHT);
 
-    // Fin = 25MHz ->/idf = 5MHz ->*2 = 10MHz ->*ndiv = 1GHz ->/2 = 500MHz ->/odf = 500MHz (500Mbps per lane); pix clk (/8) = 62.5MHz
-    let dsi_pll_config = unsafe { DsiPllConfig::manual(100, 5, 0, 4) };
+    // 500 Mbps/lane is what the OTM8009A panel on this board is driven at.
+    let dsi_pll_config = DsiPllConfig::auto(hse_freq, 500)
+        .expect("no DSI PLL config for the requested lane rate");
 
     let dsi_config = DsiConfig {
-        mode: DsiMode::Video {
-            // mode: DsiVideoMode::NonBurstWithSyncEvents,
-            mode: DsiVideoMode::Burst,
-        },
+        // Command mode: the panel is only refreshed when pushed to by
+        // `dsi_host.refresh_handle()`, synchronized to its TE output below.
+        mode: DsiMode::AdaptedCommand,
         lane_count: LaneCount::DoubleLane,
         channel: DsiChannel::Ch0,
         hse_freq,
@@ -306,7 +334,13 @@ fn main() -> ! {
     );
     dsi_host.force_rx_low_power(true);
 
-    //let mut dsi_refresh_handle = dsi_host.refresh_handle();
+    // One handle is armed by the main loop after every draw; the other is
+    // kicked from EXTI2 on the panel's TE rising edge (see `REFRESH_HANDLE`
+    // above).
+    disp.set_refresh_handle(dsi_host.refresh_handle());
+    cortex_m::interrupt::free(|cs| {
+        REFRESH_HANDLE.borrow(cs).replace(Some(dsi_host.refresh_handle()));
+    });
     info!("Initialised Display...");
 
     // Works
@@ -384,3 +418,17 @@ unsafe fn HardFault(ef: &cortex_m_rt::ExceptionFrame) -> ! {
 unsafe fn DefaultHandler(irqn: i16) {
     panic!("Unhandled exception (IRQn = {})", irqn);
 }
+
+/// Panel TE rising edge: kick the DSI wrapper to push the armed frame, if
+/// `disp.swap_layer_wait()` armed one since the last edge.
+#[interrupt]
+fn EXTI2() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(te_pin) = TE_PIN.borrow(cs).borrow_mut().as_mut() {
+            te_pin.clear_interrupt_pending_bit();
+        }
+        if let Some(handle) = REFRESH_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            handle.on_tear_effect();
+        }
+    });
+}
@@ -0,0 +1,77 @@
+//! Small drawing helpers shared between the display examples.
+
+use core::fmt::Write;
+
+use chrono::NaiveDateTime;
+use embedded_graphics::mono_font::ascii::{FONT_10X20, FONT_6X10};
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{
+    PrimitiveStyleBuilder, Rectangle, StyledDrawable,
+};
+use embedded_graphics::text::Text;
+
+use super::display_target::FrameBuffer;
+
+/// A `core::fmt::Write` sink backed by a fixed-size stack buffer, since this
+/// crate is `no_std` and has no allocator.
+struct FixedBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedBuf<N> {
+    fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+impl<const N: usize> Write for FixedBuf<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let bytes = s.as_bytes();
+        let remaining = N - self.len;
+        let n = bytes.len().min(remaining);
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+/// Draw one "Back to the Future" style time-circuit readout: a bordered
+/// panel with a caption and the date/time rendered in `digit_color` on a
+/// dimmer `background_color`.
+pub fn time_circuit(
+    date_time: NaiveDateTime,
+    x: i32,
+    y: i32,
+    caption: &str,
+    digit_color: Rgb888,
+    background_color: Rgb888,
+    draw: &mut FrameBuffer<'_>,
+) -> Result<(), core::convert::Infallible> {
+    let panel = Rectangle::new(Point::new(x, y), Size::new(280, 90));
+    let panel_style = PrimitiveStyleBuilder::new()
+        .fill_color(background_color)
+        .build();
+    panel.draw_styled(&panel_style, draw)?;
+
+    let caption_style = MonoTextStyle::new(&FONT_6X10, digit_color);
+    Text::new(caption, Point::new(x + 8, y + 14), caption_style)
+        .draw(draw)?;
+
+    let digit_style = MonoTextStyle::new(&FONT_10X20, digit_color);
+    let mut formatted = FixedBuf::<32>::new();
+    write!(formatted, "{}", date_time.format("%a %b %d  %H:%M")).ok();
+    Text::new(formatted.as_str(), Point::new(x + 8, y + 50), digit_style)
+        .draw(draw)?;
+
+    Ok(())
+}
@@ -0,0 +1,261 @@
+//! Double-buffered embedded-graphics target backed by the LTDC, with optional
+//! tearing-effect synchronization for command-mode DSI panels.
+
+use core::convert::Infallible;
+
+use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics::prelude::*;
+use embedded_graphics::Pixel;
+
+use stm32h7xx_hal::dma2d::{Buffer as Dma2dBuffer, Dma2d, PixelFormat};
+use stm32h7xx_hal::dsi::{DsiChannel, DsiHost, DsiRefreshHandle};
+use stm32h7xx_hal::ltdc::{Layer, Rectangle};
+
+/// A single framebuffer, exposed as an embedded-graphics draw target.
+///
+/// Tracks the bounding box of every pixel written since it was handed out,
+/// so a caller that only touched a small part of the frame can ask
+/// [`BufferedDisplay::swap_layer_wait_partial`] to push just that region
+/// instead of the whole framebuffer.
+pub struct FrameBuffer<'a> {
+    buf: &'a mut [u32],
+    width: usize,
+    height: usize,
+    dirty: Option<Rectangle>,
+}
+
+impl<'a> FrameBuffer<'a> {
+    /// Fill the whole buffer with black.
+    pub fn clear(&mut self) {
+        self.buf.fill(0);
+        self.mark_dirty(Rectangle {
+            x: 0,
+            y: 0,
+            width: self.width as u16,
+            height: self.height as u16,
+        });
+    }
+
+    /// The bounding box of every pixel written since this `FrameBuffer` was
+    /// handed out by [`BufferedDisplay::layer`], if any.
+    pub fn dirty(&self) -> Option<Rectangle> {
+        self.dirty
+    }
+
+    fn mark_dirty(&mut self, touched: Rectangle) {
+        self.dirty = Some(match self.dirty {
+            Some(existing) => existing.union(touched),
+            None => touched,
+        });
+    }
+}
+
+impl<'a> OriginDimensions for FrameBuffer<'a> {
+    fn size(&self) -> Size {
+        Size::new(self.width as u32, self.height as u32)
+    }
+}
+
+impl<'a> DrawTarget for FrameBuffer<'a> {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if point.x < 0 || point.y < 0 {
+                continue;
+            }
+            let (x, y) = (point.x as usize, point.y as usize);
+            if x >= self.width || y >= self.height {
+                continue;
+            }
+            let word = (color.r() as u32) << 16
+                | (color.g() as u32) << 8
+                | (color.b() as u32);
+            self.buf[y * self.width + x] = word;
+            self.mark_dirty(Rectangle {
+                x: x as u16,
+                y: y as u16,
+                width: 1,
+                height: 1,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A double-buffered LTDC display target.
+///
+/// One buffer is scanned out (or queued for the next command-mode refresh)
+/// while the other is free for the application to draw into. Call
+/// [`BufferedDisplay::layer`] to draw, then [`BufferedDisplay::swap_layer_wait`]
+/// to present the result.
+pub struct BufferedDisplay<'a> {
+    layer: Layer,
+    buffers: [&'a mut [u32]; 2],
+    width: usize,
+    height: usize,
+    active: usize,
+    /// Present for command-mode panels, where a refresh must be requested
+    /// and synchronized to the panel's tearing-effect signal rather than
+    /// happening automatically in hardware.
+    refresh: Option<DsiRefreshHandle>,
+    /// When set, the buffer just freed by a swap is cleared by DMA2D
+    /// instead of the CPU, started right after the swap so it runs while
+    /// the other buffer is being scanned out.
+    dma2d: Option<Dma2d>,
+    /// Whether `dma2d` has a fill in flight. `Dma2d::wait()` loops on a
+    /// status bit that is clear both before any fill has started and once
+    /// a finished fill has been waited for, so without this, waiting when
+    /// no fill is in flight hangs forever.
+    dma2d_pending: bool,
+    /// Whether `refresh` has a transfer armed/in flight from the last
+    /// `swap_layer_wait()`, for the same reason.
+    refresh_pending: bool,
+}
+
+impl<'a> BufferedDisplay<'a> {
+    /// Build a new double-buffered display around `layer`, with `fb1`/`fb2`
+    /// as the two `width * height` pixel backing buffers.
+    pub fn new(
+        layer: Layer,
+        fb1: &'a mut [u32],
+        fb2: &'a mut [u32],
+        width: usize,
+        height: usize,
+    ) -> Self {
+        Self {
+            layer,
+            buffers: [fb1, fb2],
+            width,
+            height,
+            active: 0,
+            refresh: None,
+            dma2d: None,
+            dma2d_pending: false,
+            refresh_pending: false,
+        }
+    }
+
+    /// Synchronize presentation to a command-mode panel's tearing-effect
+    /// signal via `handle`, instead of relying on hardware auto-refresh.
+    pub fn set_refresh_handle(&mut self, handle: DsiRefreshHandle) {
+        self.refresh = Some(handle);
+    }
+
+    /// Use `dma2d` to clear the back buffer after each swap, instead of the
+    /// CPU loop `FrameBuffer::clear()` performs.
+    pub fn set_dma2d(&mut self, dma2d: Dma2d) {
+        self.dma2d = Some(dma2d);
+    }
+
+    /// Draw into the back buffer.
+    ///
+    /// If a DMA2D clear was kicked off by the last `swap_layer_wait()`, this
+    /// waits for it to finish before handing out the buffer, so the CPU and
+    /// DMA2D never touch it at the same time. Likewise, if the last
+    /// `swap_layer_wait()` armed a command-mode refresh, this waits for the
+    /// panel's TE to have actually pushed it, so the just-freed buffer isn't
+    /// still in flight over DSI. Neither wait runs before the first-ever
+    /// swap, since there is nothing in flight yet.
+    ///
+    /// Returns the bounding box of the pixels `f` touched (see
+    /// [`FrameBuffer::dirty`]), for use with
+    /// [`BufferedDisplay::swap_layer_wait_partial`].
+    pub fn layer(
+        &mut self,
+        f: impl FnOnce(&mut FrameBuffer<'_>),
+    ) -> Option<Rectangle> {
+        if self.dma2d_pending {
+            if let Some(dma2d) = &mut self.dma2d {
+                dma2d.wait();
+            }
+            self.dma2d_pending = false;
+        }
+        if self.refresh_pending {
+            if let Some(refresh) = &mut self.refresh {
+                refresh.wait_for_refresh();
+            }
+            self.refresh_pending = false;
+        }
+
+        let back = 1 - self.active;
+        let mut target = FrameBuffer {
+            buf: self.buffers[back],
+            width: self.width,
+            height: self.height,
+            dirty: None,
+        };
+        f(&mut target);
+        target.dirty
+    }
+
+    /// Present the back buffer, and arm (but do not wait out) its handoff to
+    /// the panel.
+    ///
+    /// In video mode this only has to program the new scan-out address; the
+    /// LTDC picks it up at the next vertical blank on its own. In command
+    /// mode (when [`BufferedDisplay::set_refresh_handle`] has been called)
+    /// this arms the refresh handle instead of forcing an immediate
+    /// transfer, so the actual push only happens on the panel's own TE
+    /// edge; the next [`BufferedDisplay::layer`] call is what waits for it
+    /// to have completed, instead of busy-waiting here.
+    pub fn swap_layer_wait(&mut self) {
+        self.active = 1 - self.active;
+        let addr = self.buffers[self.active].as_ptr() as u32;
+
+        unsafe {
+            self.layer.set_buffer_address(addr);
+        }
+
+        if let Some(refresh) = &mut self.refresh {
+            refresh.arm_on_tear_effect();
+            self.refresh_pending = true;
+        }
+
+        if let Some(dma2d) = &mut self.dma2d {
+            let back = 1 - self.active;
+            let buffer = Dma2dBuffer {
+                addr: self.buffers[back].as_ptr() as u32,
+                line_stride: self.width as u16,
+                format: PixelFormat::Argb8888,
+            };
+            dma2d.fill_rect(buffer, self.width as u16, self.height as u16, 0);
+            dma2d.start();
+            self.dma2d_pending = true;
+        }
+    }
+
+    /// Like [`BufferedDisplay::swap_layer_wait`], but for command-mode
+    /// panels where only `window` (typically the bounding box returned by
+    /// [`BufferedDisplay::layer`]) changed: only that sub-rectangle is
+    /// streamed to the panel, instead of the full frame.
+    ///
+    /// `window` must be expressed in the framebuffer's own coordinate
+    /// space, i.e. with the panel's rotation already accounted for by the
+    /// caller.
+    pub fn swap_layer_wait_partial(
+        &mut self,
+        dsi_host: &mut DsiHost,
+        channel: DsiChannel,
+        window: Rectangle,
+    ) {
+        self.active = 1 - self.active;
+        let base = self.buffers[self.active].as_ptr() as u32;
+
+        unsafe {
+            self.layer.set_partial_buffer(
+                base,
+                self.width as u16,
+                4, // bytes per pixel (u32 word per pixel)
+                window,
+            );
+        }
+
+        dsi_host.refresh_partial(channel, window);
+        dsi_host.wait_for_refresh();
+    }
+}
@@ -0,0 +1,2 @@
+pub mod display_primitives;
+pub mod display_target;
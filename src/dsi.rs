@@ -0,0 +1,709 @@
+//! Display Serial Interface (DSI) host.
+//!
+//! The H7 DSI host drives an external DSI panel (directly, or through a
+//! bridge chip) either in video mode -- where the LTDC continuously streams
+//! frames and the panel is refreshed by hardware -- or in adapted command
+//! mode, where frames are pushed explicitly over the DSI link.
+//!
+//! See RM0433 rev 7 chapter 32 "DSI Host controller (DSIHOST)".
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ltdc::Rectangle;
+use crate::rcc::{rec, CoreClocks};
+use crate::stm32::dsihost::RegisterBlock;
+use crate::stm32::DSIHOST;
+use crate::time::Hertz;
+
+/// DCS "column address set" command.
+const DCS_CASET: u8 = 0x2A;
+/// DCS "page address set" command.
+const DCS_PASET: u8 = 0x2B;
+
+/// Maximum payload this module's `dcs_long_write` supports (a command byte
+/// plus up to 15 parameter bytes), which comfortably covers CASET/PASET's
+/// 4-byte windows.
+const DCS_LONG_WRITE_MAX_LEN: usize = 16;
+
+/// Send a DCS long-write packet (generic header + payload FIFO): `cmd`
+/// followed by `params`, pushed through the generic payload data register
+/// four bytes at a time.
+fn dcs_long_write(
+    rb: &RegisterBlock,
+    channel: DsiChannel,
+    cmd: u8,
+    params: &[u8],
+) {
+    let len = 1 + params.len();
+    debug_assert!(len <= DCS_LONG_WRITE_MAX_LEN);
+
+    let mut payload = [0u8; DCS_LONG_WRITE_MAX_LEN];
+    payload[0] = cmd;
+    payload[1..len].copy_from_slice(params);
+
+    for word in payload[..len].chunks(4) {
+        let mut bytes = [0u8; 4];
+        bytes[..word.len()].copy_from_slice(word);
+        rb.gpdr().write(|w| unsafe { w.bits(u32::from_le_bytes(bytes)) });
+    }
+
+    rb.ghcr().write(|w| unsafe {
+        w.dt()
+            .bits(0x39) // DCS long write
+            .vcid()
+            .bits(channel as u8)
+            .wclsb()
+            .bits((len & 0xFF) as u8)
+            .wcmsb()
+            .bits(((len >> 8) & 0xFF) as u8)
+    });
+}
+
+/// Errors that can occur while configuring or driving the DSI host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsiError {
+    /// No `(ndiv, idf, odf)` combination was found that satisfies the PLL
+    /// phase-detector and VCO bounds for the requested per-lane bit rate.
+    PllConfigNotFound,
+    /// The DSI PHY did not report "ready" within the expected time.
+    PhyNotReady,
+}
+
+/// Number of physical data lanes used on the DSI link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaneCount {
+    /// Single data lane.
+    SingleLane,
+    /// Two data lanes (most common on the H747I-DISCO).
+    DoubleLane,
+}
+
+/// Which of the four virtual DSI channels a peripheral talks over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsiChannel {
+    Ch0,
+    Ch1,
+    Ch2,
+    Ch3,
+}
+
+/// Pixel color coding used on the wrapper (LTDC-facing) or host (DSI-facing)
+/// side of the link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorCoding {
+    SixteenBitsConfig1,
+    SixteenBitsConfig2,
+    SixteenBitsConfig3,
+    EighteenBitsConfig1,
+    EighteenBitsConfig2,
+    TwentyFourBits,
+}
+
+/// Sub-mode used while streaming frames in [`DsiMode::Video`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsiVideoMode {
+    NonBurstWithSyncPulses,
+    NonBurstWithSyncEvents,
+    Burst,
+}
+
+/// Overall operating mode of the DSI link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsiMode {
+    /// The LTDC drives the panel continuously; the DSI host just re-packs
+    /// pixels as they arrive.
+    Video { mode: DsiVideoMode },
+    /// Frames are only pushed to the panel when software (or a tearing
+    /// effect signal) requests a refresh.
+    AdaptedCommand,
+}
+
+/// How DCS commands are transmitted on the link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsiCmdModeTransmissionKind {
+    /// Every command is sent in low power mode.
+    AllInLowPower,
+    /// Every command is sent in high speed mode.
+    AllInHighSpeed,
+}
+
+/// Which DSI host interrupt sources are enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsiInterrupts {
+    /// No DSI host interrupts enabled.
+    None,
+    /// Enable the interrupts needed to track tearing-effect-synchronized
+    /// command mode refreshes (end-of-refresh and error reporting).
+    RefreshHandling,
+}
+
+/// D-PHY timing parameters, in units of the PHY's high-speed byte clock.
+///
+/// These correspond directly to the `PCTLR`/`PCONFR`-adjacent timer fields
+/// documented in RM0433; refer to the panel's D-PHY timing datasheet page to
+/// derive them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DsiPhyTimers {
+    pub dataline_hs2lp: u8,
+    pub dataline_lp2hs: u8,
+    pub clock_hs2lp: u16,
+    pub clock_lp2hs: u16,
+    pub dataline_max_read_time: u16,
+    pub stop_wait_time: u8,
+}
+
+/// DSI host configuration, independent of the PLL dividers.
+#[derive(Debug, Clone)]
+pub struct DsiConfig {
+    pub mode: DsiMode,
+    pub lane_count: LaneCount,
+    pub channel: DsiChannel,
+    pub hse_freq: Hertz,
+    pub ltdc_freq: Hertz,
+    pub interrupts: DsiInterrupts,
+    pub color_coding_host: ColorCoding,
+    pub color_coding_wrapper: ColorCoding,
+    pub lp_size: u8,
+    pub vlp_size: u8,
+}
+
+/// DSI D-PHY PLL divider configuration.
+///
+/// The DSI host has a dedicated PLL that generates the D-PHY high speed
+/// clock directly from `HSE`, independent of the RCC PLLs. The relation
+/// between the dividers and the resulting per-lane bit clock is:
+///
+/// ```text
+/// f_vco  = (f_hse / idf) * 2 * ndiv
+/// f_lane = f_vco / (2 * odf)
+/// ```
+///
+/// with the constraints (RM0433 section 32.8.2):
+/// - the phase detector input, `f_hse / idf`, must lie in 4-8 MHz
+/// - `f_vco` must lie in 500 MHz-1 GHz
+/// - `ndiv` in 10..=125, `idf` in 1..=7, `odf` one of 1, 2, 4, 8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DsiPllConfig {
+    ndiv: u16,
+    idf: u8,
+    odf: u8,
+    tx_escape_ckdiv: u8,
+}
+
+const IDF_RANGE: core::ops::RangeInclusive<u8> = 1..=7;
+const NDIV_RANGE: core::ops::RangeInclusive<u16> = 10..=125;
+const ODF_VALUES: [u8; 4] = [1, 2, 4, 8];
+const PHASE_DETECTOR_INPUT_RANGE: core::ops::RangeInclusive<u32> =
+    4_000_000..=8_000_000;
+const VCO_RANGE: core::ops::RangeInclusive<u32> = 500_000_000..=1_000_000_000;
+/// Maximum recommended DSI low-power ("escape") clock frequency.
+const MAX_TX_ESCAPE_CLOCK: u32 = 20_000_000;
+
+impl DsiPllConfig {
+    /// Build a PLL configuration directly from the raw divider values,
+    /// bypassing the search and bounds-checking performed by
+    /// [`DsiPllConfig::auto`].
+    ///
+    /// `odf` is the register-field encoding of the output divider (0 => /1,
+    /// 1 => /2, 2 => /4, 3 => /8), matching `PCONFR.ODF`.
+    ///
+    /// # Safety
+    /// The caller is responsible for ensuring `ndiv`/`idf`/`odf` satisfy the
+    /// PLL phase-detector and VCO constraints documented on
+    /// [`DsiPllConfig`]; out-of-range values can leave the DSI PLL unable to
+    /// lock, or generate a lane clock far outside the panel's ratings.
+    pub unsafe fn manual(
+        ndiv: u16,
+        idf: u8,
+        odf: u8,
+        tx_escape_ckdiv: u8,
+    ) -> Self {
+        Self {
+            ndiv,
+            idf,
+            odf,
+            tx_escape_ckdiv,
+        }
+    }
+
+    /// Search for the `(ndiv, idf, odf)` triple that brings the per-lane bit
+    /// clock as close as possible to `target_lane_mbps` megabits/second,
+    /// given the board's `hse_freq`, honouring the PLL's phase-detector and
+    /// VCO bounds.
+    pub fn auto(
+        hse_freq: Hertz,
+        target_lane_mbps: u32,
+    ) -> Result<Self, DsiError> {
+        let f_in = hse_freq.raw();
+        let target_hz = target_lane_mbps as u64 * 1_000_000;
+
+        let mut best: Option<(u16, u8, u8, u64)> = None;
+
+        for idf in IDF_RANGE {
+            let f_pd = f_in / idf as u32;
+            if !PHASE_DETECTOR_INPUT_RANGE.contains(&f_pd) {
+                continue;
+            }
+
+            for &odf in &ODF_VALUES {
+                // ndiv that lands f_vco / (2 * odf) closest to the target:
+                // ndiv = target_lane_hz * odf * idf / f_in
+                let ndiv_ideal =
+                    (target_hz * odf as u64 * idf as u64) / f_in as u64;
+
+                // The true optimum is never more than one integer step away
+                // from the rounded-down ideal value, so just check its
+                // neighbourhood.
+                for cand in [
+                    ndiv_ideal.saturating_sub(1),
+                    ndiv_ideal,
+                    ndiv_ideal + 1,
+                ] {
+                    if cand < *NDIV_RANGE.start() as u64
+                        || cand > *NDIV_RANGE.end() as u64
+                    {
+                        continue;
+                    }
+                    let ndiv = cand as u16;
+
+                    let f_vco =
+                        (f_in as u64 / idf as u64) * 2 * ndiv as u64;
+                    if !VCO_RANGE.contains(&(f_vco as u32))
+                        || f_vco > u32::MAX as u64
+                    {
+                        continue;
+                    }
+
+                    let f_lane = f_vco / (2 * odf as u64);
+                    let err = f_lane.abs_diff(target_hz);
+
+                    // On a tie, the later candidate (larger `idf`) wins.
+                    let better = match best {
+                        Some((_, _, _, best_err)) => err <= best_err,
+                        None => true,
+                    };
+                    if better {
+                        best = Some((ndiv, idf, odf, err));
+                    }
+                }
+            }
+        }
+
+        let (ndiv, idf, odf, _) =
+            best.ok_or(DsiError::PllConfigNotFound)?;
+
+        let odf_field = match odf {
+            1 => 0,
+            2 => 1,
+            4 => 2,
+            8 => 3,
+            _ => unreachable!(),
+        };
+        // The escape clock is the lane byte clock (lane bit clock / 8)
+        // divided by `TXECKDIV`.
+        let f_lane =
+            (f_in as u64 / idf as u64) * 2 * ndiv as u64 / (2 * odf as u64);
+        let lane_byte_clk = (f_lane / 8) as u32;
+        let tx_escape_ckdiv = ((lane_byte_clk + MAX_TX_ESCAPE_CLOCK - 1)
+            / MAX_TX_ESCAPE_CLOCK)
+            .max(1) as u8;
+
+        // SAFETY: ndiv/idf/odf were all verified against the PLL bounds
+        // above.
+        Ok(unsafe {
+            Self::manual(ndiv, idf, odf_field, tx_escape_ckdiv)
+        })
+    }
+}
+
+/// DSI host driver.
+pub struct DsiHost {
+    rb: DSIHOST,
+}
+
+impl DsiHost {
+    /// Initialise the DSI host and its D-PHY PLL, and configure the link for
+    /// `dsi_config` against `display_config`'s timings.
+    pub fn init(
+        pll_config: DsiPllConfig,
+        display_config: embedded_display_controller::DisplayConfiguration,
+        dsi_config: DsiConfig,
+        dsihost: DSIHOST,
+        prec: rec::Dsi,
+        clocks: &CoreClocks,
+    ) -> Result<Self, DsiError> {
+        let _ = clocks;
+        prec.enable();
+
+        dsihost.wrpcr().modify(|_, w| unsafe {
+            w.ndiv()
+                .bits(pll_config.ndiv as u8)
+                .idf()
+                .bits(pll_config.idf)
+                .odf()
+                .bits(pll_config.odf)
+        });
+        dsihost.wrpcr().modify(|_, w| w.pllen().set_bit());
+
+        // Wait for the PLL to lock.
+        let mut timeout = 100_000;
+        while dsihost.wisr().read().pllls().bit_is_clear() {
+            timeout -= 1;
+            if timeout == 0 {
+                return Err(DsiError::PhyNotReady);
+            }
+        }
+
+        dsihost
+            .ccr()
+            .modify(|_, w| unsafe { w.txeckdiv().bits(pll_config.tx_escape_ckdiv) });
+
+        // Largest low-power packet the host may send per command-mode
+        // refresh (`LPSIZE`) and per video-mode porch/blanking window
+        // (`VLPSIZE`), in bytes.
+        dsihost.lpmcr().modify(|_, w| unsafe {
+            w.lpsize()
+                .bits(dsi_config.lp_size)
+                .vlpsize()
+                .bits(dsi_config.vlp_size)
+        });
+
+        match dsi_config.mode {
+            DsiMode::Video { mode } => {
+                dsihost.mcr().modify(|_, w| w.cmdm().clear_bit());
+                dsihost.vmcr().modify(|_, w| unsafe {
+                    w.vmt().bits(match mode {
+                        DsiVideoMode::NonBurstWithSyncPulses => 0b00,
+                        DsiVideoMode::NonBurstWithSyncEvents => 0b01,
+                        DsiVideoMode::Burst => 0b10,
+                    })
+                });
+                Self::configure_video_timing(&dsihost, &display_config, &dsi_config);
+            }
+            DsiMode::AdaptedCommand => {
+                dsihost.mcr().modify(|_, w| w.cmdm().set_bit());
+            }
+        }
+
+        match dsi_config.interrupts {
+            DsiInterrupts::None => {}
+            DsiInterrupts::RefreshHandling => {
+                // Wrapper "end of refresh" interrupt: fires once a
+                // `DsiRefreshHandle`-triggered transfer (command or partial
+                // mode) has actually completed, the interrupt-driven
+                // counterpart to polling `WISR.BUSY`.
+                dsihost.wier().modify(|_, w| w.erie().set_bit());
+                // Host-level protocol/PHY/timeout error sources (RM0433
+                // section 32.8.11-12); none of IER0/IER1 is reserved, so
+                // enabling every bit reports every error the wrapper can
+                // detect during that same refresh.
+                dsihost.ier0().write(|w| unsafe { w.bits(0xFFFF_FFFF) });
+                dsihost.ier1().write(|w| unsafe { w.bits(0xFFFF_FFFF) });
+            }
+        }
+
+        Ok(Self { rb: dsihost })
+    }
+
+    /// Program the wrapper's video-mode timing registers (`VPCR`/`VHSACR`/
+    /// `VHBPCR`/`VLCR`/`VVSACR`/`VVBPCR`/`VVFPCR`/`VVACR`) from
+    /// `display_config`.
+    ///
+    /// The LTDC counts these timings in pixels; the DSI wrapper counts them
+    /// in units of a byte lane, so the horizontal sync, back porch and line
+    /// length are rescaled by `bits_per_pixel / (8 * lane_count)` before
+    /// being written; `VPSIZE` stays in pixels and the vertical timings in
+    /// lines (RM0433
+    /// section 32.8.6, "Video mode timings").
+    fn configure_video_timing(
+        dsihost: &DSIHOST,
+        display_config: &embedded_display_controller::DisplayConfiguration,
+        dsi_config: &DsiConfig,
+    ) {
+        let lanes = match dsi_config.lane_count {
+            LaneCount::SingleLane => 1u32,
+            LaneCount::DoubleLane => 2u32,
+        };
+        let bits_per_pixel = match dsi_config.color_coding_wrapper {
+            ColorCoding::SixteenBitsConfig1
+            | ColorCoding::SixteenBitsConfig2
+            | ColorCoding::SixteenBitsConfig3 => 16u32,
+            ColorCoding::EighteenBitsConfig1
+            | ColorCoding::EighteenBitsConfig2 => 18u32,
+            ColorCoding::TwentyFourBits => 24u32,
+        };
+        let to_lane_units =
+            |pixels: u16| -> u16 { (pixels as u32 * bits_per_pixel / (8 * lanes)) as u16 };
+
+        dsihost
+            .vpcr()
+            .write(|w| unsafe { w.vpsize().bits(display_config.active_width) });
+        // VCCR.NUMC ("number of chunks per line") only applies to chunked
+        // non-burst-with-sync-pulses transfers, which this driver doesn't
+        // use; leave it at its reset default of 0 (unchunked) rather than
+        // stuffing the active-line count into it.
+        dsihost
+            .vhsacr()
+            .write(|w| unsafe { w.hsa().bits(to_lane_units(display_config.h_sync)) });
+        dsihost.vhbpcr().write(|w| unsafe {
+            w.hbp().bits(to_lane_units(display_config.h_back_porch))
+        });
+        dsihost.vlcr().write(|w| unsafe {
+            w.hline().bits(to_lane_units(
+                display_config.h_sync
+                    + display_config.h_back_porch
+                    + display_config.active_width
+                    + display_config.h_front_porch,
+            ))
+        });
+        dsihost
+            .vvsacr()
+            .write(|w| unsafe { w.vsa().bits(display_config.v_sync as u8) });
+        dsihost
+            .vvbpcr()
+            .write(|w| unsafe { w.vbp().bits(display_config.v_back_porch as u8) });
+        dsihost
+            .vvfpcr()
+            .write(|w| unsafe { w.vfp().bits(display_config.v_front_porch as u8) });
+        dsihost
+            .vvacr()
+            .write(|w| unsafe { w.va().bits(display_config.active_height) });
+    }
+
+    /// Enable the DSI host and wrapper.
+    pub fn start(&mut self) {
+        self.rb.cr().modify(|_, w| w.en().set_bit());
+        self.rb.wcr().modify(|_, w| w.dsien().set_bit());
+    }
+
+    /// Allow the host to initiate bus-turn-around (required before any read
+    /// command is sent to the panel).
+    pub fn enable_bus_turn_around(&mut self) {
+        self.rb.pcr().modify(|_, w| w.bta_en().set_bit());
+    }
+
+    /// Force the PHY's data lanes to low power mode for reception, instead
+    /// of high speed, which some panels require for reliable reads.
+    ///
+    /// This is a separate control from [`DsiHost::enable_bus_turn_around`]
+    /// (`PCR.BTAE`, which just permits the link to turn the bus around at
+    /// all): it is the wrapper's `WPCR0.FLPRXLPM` low-power-RX-force field that
+    /// decides which speed the reply is actually captured at.
+    pub fn force_rx_low_power(&mut self, enable: bool) {
+        self.rb.wpcr0().modify(|_, w| w.flprxlpm().bit(enable));
+    }
+
+    /// Program the D-PHY timers controlling the high-speed/low-power
+    /// transition timings.
+    pub fn configure_phy_timers(&mut self, timers: DsiPhyTimers) {
+        self.rb.dlhstcr().modify(|_, w| unsafe {
+            w.hstx().bits(timers.dataline_hs2lp)
+        });
+        self.rb.dlltcr().modify(|_, w| unsafe {
+            w.lptx().bits(timers.dataline_lp2hs)
+        });
+        self.rb.pconfr().modify(|_, w| unsafe {
+            w.sw_time().bits(timers.stop_wait_time)
+        });
+        // Clock lane HS<->LP transition timings.
+        self.rb.cltcr().modify(|_, w| unsafe {
+            w.hs2lp_time()
+                .bits(timers.clock_hs2lp)
+                .lp2hs_time()
+                .bits(timers.clock_lp2hs)
+        });
+        // Timeout counter for a data lane read: how long the host waits
+        // for the panel's reply before giving up.
+        self.rb.tccr3().modify(|_, w| unsafe {
+            w.hsrd_tocnt().bits(timers.dataline_max_read_time)
+        });
+    }
+
+    /// Select whether DCS commands are sent in low power or high speed mode.
+    pub fn set_command_mode_transmission_kind(
+        &mut self,
+        kind: DsiCmdModeTransmissionKind,
+    ) {
+        let lp = matches!(kind, DsiCmdModeTransmissionKind::AllInLowPower);
+        self.rb.cmcr().modify(|_, w| {
+            w.gelpvct()
+                .bit(lp)
+                .gelpwct()
+                .bit(lp)
+                .gelprct()
+                .bit(lp)
+        });
+    }
+
+    /// Create a tearing-effect-synchronized refresh handle for this host.
+    ///
+    /// Intended for [`DsiMode::AdaptedCommand`] panels: call this after
+    /// [`DsiHost::init`], then drive it from the panel's TE GPIO interrupt.
+    /// Every handle shares the same armed state, so one handle can be armed
+    /// from the main loop while another is kicked from the interrupt.
+    pub fn refresh_handle(&self) -> DsiRefreshHandle {
+        DsiRefreshHandle {
+            rb: unsafe { &*DSIHOST::ptr() },
+        }
+    }
+
+    /// Program the panel's column-address (`CASET`, `0x2A`) and
+    /// page-address (`PASET`, `0x2B`) DCS windows to `window`, so the next
+    /// manual refresh only touches that sub-rectangle.
+    ///
+    /// `window` must already be in the panel's own coordinate space (i.e.
+    /// with the configured rotation/[`DsiMode`] already applied by the
+    /// caller) -- this only talks to the panel, it has no notion of
+    /// rotation itself.
+    pub fn set_refresh_window(
+        &mut self,
+        channel: DsiChannel,
+        window: Rectangle,
+    ) {
+        let x0 = window.x;
+        let x1 = window.x + window.width - 1;
+        let y0 = window.y;
+        let y1 = window.y + window.height - 1;
+
+        dcs_long_write(
+            &self.rb,
+            channel,
+            DCS_CASET,
+            &[(x0 >> 8) as u8, x0 as u8, (x1 >> 8) as u8, x1 as u8],
+        );
+        dcs_long_write(
+            &self.rb,
+            channel,
+            DCS_PASET,
+            &[(y0 >> 8) as u8, y0 as u8, (y1 >> 8) as u8, y1 as u8],
+        );
+    }
+
+    /// Poll whether the last triggered wrapper refresh has finished
+    /// transferring.
+    pub fn is_refresh_done(&self) -> bool {
+        self.rb.wisr().read().busy().bit_is_clear()
+    }
+
+    /// Program `window` via [`DsiHost::set_refresh_window`] and immediately
+    /// kick off a single wrapper transfer of just that sub-rectangle.
+    ///
+    /// The caller is responsible for having already pointed the LTDC layer
+    /// at the matching clipped source region (see
+    /// [`crate::ltdc::Layer::set_partial_buffer`]) before calling this.
+    pub fn refresh_partial(&mut self, channel: DsiChannel, window: Rectangle) {
+        self.set_refresh_window(channel, window);
+        self.rb.wcr().modify(|_, w| w.ltdcen().set_bit());
+    }
+
+    /// Block until the refresh started by [`DsiHost::refresh_partial`]
+    /// both starts and finishes.
+    ///
+    /// `WISR.BUSY` is not guaranteed to assert in the same cycle as
+    /// `WCR.LTDCEN`, so a bare `while !is_refresh_done() {}` right after
+    /// kicking a transfer can observe `BUSY` still clear from the *previous*
+    /// refresh and return immediately. Waiting for `BUSY` to assert first
+    /// removes that race.
+    pub fn wait_for_refresh(&self) {
+        while self.rb.wisr().read().busy().bit_is_clear() {}
+        while self.rb.wisr().read().busy().bit_is_set() {}
+    }
+}
+
+/// A handle used to synchronize command-mode frame pushes with the panel's
+/// tearing-effect (TE) signal.
+///
+/// The handle does not own the DSI host's register block exclusively (the
+/// host itself still drives configuration), but only ever touches the
+/// wrapper enable/status bits needed to kick off and observe a refresh, so
+/// it can be moved into an EXTI interrupt handler independently of the rest
+/// of [`DsiHost`].
+pub struct DsiRefreshHandle {
+    rb: &'static crate::stm32::dsihost::RegisterBlock,
+}
+
+/// Set by [`DsiRefreshHandle::arm_on_tear_effect`] on any handle, consumed by
+/// the next [`DsiRefreshHandle::on_tear_effect`] call on any handle.
+static REFRESH_ARMED: AtomicBool = AtomicBool::new(false);
+
+// SAFETY: DsiRefreshHandle only ever touches the WCR/WISR registers, which
+// are disjoint from the ones DsiHost's other methods use once the link is
+// running, so sharing it with an interrupt context is sound.
+unsafe impl Send for DsiRefreshHandle {}
+
+impl DsiRefreshHandle {
+    /// Arm the next TE rising edge to trigger exactly one frame refresh.
+    ///
+    /// This only latches intent; it does not touch the wrapper itself. Call
+    /// it once a new frame is ready, then call
+    /// [`DsiRefreshHandle::on_tear_effect`] from the TE GPIO/EXTI interrupt
+    /// handler to actually start the transfer on the next TE edge.
+    pub fn arm_on_tear_effect(&mut self) {
+        REFRESH_ARMED.store(true, Ordering::Release);
+    }
+
+    /// Call from the TE GPIO/EXTI interrupt handler on every rising edge.
+    ///
+    /// Starts the wrapper's one-shot transfer of the current LTDC frame to
+    /// the panel only if [`DsiRefreshHandle::arm_on_tear_effect`] was called
+    /// (on this or any other handle) since the last refresh; otherwise the
+    /// edge is ignored. Either way, the handles are left disarmed, so the
+    /// caller must re-arm before the next edge should trigger a push.
+    pub fn on_tear_effect(&mut self) {
+        if REFRESH_ARMED.swap(false, Ordering::AcqRel) {
+            self.rb.wcr().modify(|_, w| w.ltdcen().set_bit());
+        }
+    }
+
+    /// Force a refresh immediately, without waiting for the next TE edge.
+    ///
+    /// Useful for the very first frame, or for panels/bring-up where TE is
+    /// not wired up yet. Does not consume a pending `arm_on_tear_effect`.
+    pub fn refresh_now(&mut self) {
+        self.rb.wcr().modify(|_, w| w.ltdcen().set_bit());
+    }
+
+    /// Poll whether the last triggered refresh has finished transferring.
+    pub fn is_refresh_done(&self) -> bool {
+        self.rb.wisr().read().busy().bit_is_clear()
+    }
+
+    /// Block until a refresh triggered by [`DsiRefreshHandle::refresh_now`]
+    /// or [`DsiRefreshHandle::on_tear_effect`] both starts and finishes.
+    ///
+    /// `WISR.BUSY` is not guaranteed to assert in the same cycle as
+    /// `WCR.LTDCEN`, so a bare `while !is_refresh_done() {}` right after
+    /// kicking a transfer can observe `BUSY` still clear from the *previous*
+    /// refresh and return immediately. Waiting for `BUSY` to assert first
+    /// removes that race.
+    pub fn wait_for_refresh(&self) {
+        while self.rb.wisr().read().busy().bit_is_clear() {}
+        while self.rb.wisr().read().busy().bit_is_set() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_25mhz_hse_500mbps_matches_known_good_manual_config() {
+        // This is the exact (ndiv, idf, odf) triple this board used before
+        // `DsiPllConfig::auto` existed: 25MHz/5 = 5MHz phase detector input,
+        // *2*100 = 1GHz VCO, /(2*1) = 500Mbps/lane, dead on target.
+        let cfg = DsiPllConfig::auto(Hertz::from_raw(25_000_000), 500).unwrap();
+        assert_eq!(cfg.idf, 5);
+        assert_eq!(cfg.ndiv, 100);
+        assert_eq!(cfg.odf, 0);
+    }
+
+    #[test]
+    fn auto_rejects_unreachable_lane_rate() {
+        // 1Mbps/lane is far below anything the VCO/phase-detector bounds
+        // can reach from a 25MHz HSE.
+        assert_eq!(
+            DsiPllConfig::auto(Hertz::from_raw(25_000_000), 1),
+            Err(DsiError::PllConfigNotFound)
+        );
+    }
+}
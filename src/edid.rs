@@ -0,0 +1,168 @@
+//! EDID (Extended Display Identification Data) reading over DDC I2C.
+//!
+//! Lets an RGB (HDMI/DVI-style) panel's [`DisplayConfiguration`][dc] for
+//! [`crate::ltdc`] be read straight out of the monitor instead of copied by
+//! hand from a datasheet.
+//!
+//! [dc]: embedded_display_controller::DisplayConfiguration
+
+use embedded_display_controller::DisplayConfiguration;
+use embedded_hal::blocking::i2c::WriteRead;
+
+use crate::time::Hertz;
+
+/// The fixed 7-bit DDC/EDID I2C address every compliant display answers on.
+pub const EDID_I2C_ADDRESS: u8 = 0x50;
+
+/// Errors that can occur while reading or decoding EDID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdidError<E> {
+    /// The underlying I2C transaction failed.
+    I2c(E),
+    /// The EDID magic header (`00 FF FF FF FF FF FF 00`) was not present.
+    InvalidHeader,
+    /// The 128-byte block's checksum did not sum to 0 mod 256.
+    InvalidChecksum,
+    /// The first Detailed Timing Descriptor slot held a non-timing
+    /// descriptor (e.g. a monitor name/serial/range-limits descriptor)
+    /// instead of a Detailed Timing Descriptor.
+    NotATimingDescriptor,
+    /// The descriptor claimed to be a Detailed Timing Descriptor (non-zero
+    /// pixel clock), but its blanking/front-porch/sync fields are internally
+    /// inconsistent (front porch + sync width exceeds the total blanking
+    /// interval), so no back porch can be derived from them.
+    InvalidTiming,
+}
+
+/// A decoded EDID Detailed Timing Descriptor.
+#[derive(Debug, Clone, Copy)]
+pub struct EdidTiming {
+    /// LTDC porch/sync configuration, ready for [`crate::ltdc::Ltdc::init`].
+    pub config: DisplayConfiguration,
+    /// The panel's requested pixel clock.
+    pub pixel_clock: Hertz,
+}
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+/// Offset of the first Detailed Timing Descriptor within the 128-byte EDID
+/// base block.
+const FIRST_DTD_OFFSET: usize = 54;
+const DTD_LEN: usize = 18;
+
+/// Read the 128-byte EDID base block from `i2c` and decode its first
+/// Detailed Timing Descriptor.
+///
+/// Validates the EDID header and whole-block checksum before trusting any
+/// of the decoded fields.
+pub fn read_display_configuration<I2C, E>(
+    i2c: &mut I2C,
+) -> Result<EdidTiming, EdidError<E>>
+where
+    I2C: WriteRead<Error = E>,
+{
+    let mut edid = [0u8; 128];
+    i2c.write_read(EDID_I2C_ADDRESS, &[0x00], &mut edid)
+        .map_err(EdidError::I2c)?;
+
+    if edid[..8] != EDID_HEADER {
+        return Err(EdidError::InvalidHeader);
+    }
+
+    let checksum = edid.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+    if checksum != 0 {
+        return Err(EdidError::InvalidChecksum);
+    }
+
+    let dtd = &edid[FIRST_DTD_OFFSET..FIRST_DTD_OFFSET + DTD_LEN];
+    decode_detailed_timing(dtd)
+}
+
+fn decode_detailed_timing<E>(b: &[u8]) -> Result<EdidTiming, EdidError<E>> {
+    let pixel_clock_10khz = u16::from_le_bytes([b[0], b[1]]) as u32;
+    // A pixel clock of 0 marks this 18-byte slot as a display descriptor
+    // (monitor name/serial/range limits/...) rather than a Detailed Timing
+    // Descriptor; every other field below is meaningless in that case.
+    if pixel_clock_10khz == 0 {
+        return Err(EdidError::NotATimingDescriptor);
+    }
+
+    let h_active = b[2] as u16 | (((b[4] & 0xF0) as u16) << 4);
+    let h_blank = b[3] as u16 | (((b[4] & 0x0F) as u16) << 8);
+    let v_active = b[5] as u16 | (((b[7] & 0xF0) as u16) << 4);
+    let v_blank = b[6] as u16 | (((b[7] & 0x0F) as u16) << 8);
+
+    let h_front_porch = b[8] as u16 | (((b[11] & 0xC0) as u16) << 2);
+    let h_sync = b[9] as u16 | (((b[11] & 0x30) as u16) << 4);
+    let v_front_porch =
+        (b[10] >> 4) as u16 | (((b[11] & 0x0C) as u16) << 2);
+    let v_sync = (b[10] & 0x0F) as u16 | (((b[11] & 0x03) as u16) << 4);
+
+    let h_back_porch = h_blank
+        .checked_sub(h_front_porch)
+        .and_then(|r| r.checked_sub(h_sync))
+        .ok_or(EdidError::InvalidTiming)?;
+    let v_back_porch = v_blank
+        .checked_sub(v_front_porch)
+        .and_then(|r| r.checked_sub(v_sync))
+        .ok_or(EdidError::InvalidTiming)?;
+
+    // Byte 17: bits [4:3] select the sync type; `0b11` is "digital
+    // separate", the only encoding that carries independent h/v sync
+    // polarities (bit 2 = vsync, bit 1 = hsync, both active-high when set).
+    let flags = b[17];
+    let (h_sync_pol, v_sync_pol) = if (flags >> 3) & 0x3 == 0b11 {
+        (flags & 0b0010 != 0, flags & 0b0100 != 0)
+    } else {
+        (false, false)
+    };
+
+    Ok(EdidTiming {
+        config: DisplayConfiguration {
+            active_width: h_active,
+            active_height: v_active,
+            h_back_porch,
+            h_front_porch,
+            v_back_porch,
+            v_front_porch,
+            h_sync,
+            v_sync,
+            h_sync_pol,
+            v_sync_pol,
+            not_data_enable_pol: false,
+            pixel_clock_pol: false,
+        },
+        pixel_clock: Hertz::from_raw(pixel_clock_10khz * 10_000),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_detailed_timing_rejects_a_display_descriptor() {
+        // A legal base-block layout where the first DTD slot actually holds
+        // a display descriptor (pixel clock word == 0, e.g. a monitor name)
+        // instead of a Detailed Timing Descriptor.
+        let descriptor = [0u8; DTD_LEN];
+        let result: Result<EdidTiming, EdidError<()>> =
+            decode_detailed_timing(&descriptor);
+        assert!(matches!(result, Err(EdidError::NotATimingDescriptor)));
+    }
+
+    #[test]
+    fn decode_detailed_timing_rejects_inconsistent_blanking() {
+        // Non-zero pixel clock (so this is a real Detailed Timing
+        // Descriptor), but a blanking interval narrower than its own
+        // front-porch + sync-width -- malformed data an external I2C device
+        // should not be trusted to never produce.
+        let mut descriptor = [0u8; DTD_LEN];
+        descriptor[0] = 0x10; // pixel clock, non-zero
+        descriptor[3] = 0; // h_blank low byte
+        descriptor[8] = 10; // h_front_porch
+        descriptor[9] = 5; // h_sync
+        let result: Result<EdidTiming, EdidError<()>> =
+            decode_detailed_timing(&descriptor);
+        assert!(matches!(result, Err(EdidError::InvalidTiming)));
+    }
+}
@@ -0,0 +1,334 @@
+//! LCD-TFT Display Controller (LTDC).
+//!
+//! Drives a parallel RGB (or, through the DSI wrapper, DSI) panel from a
+//! framebuffer in memory. See RM0433 rev 7 chapter 33 "LCD-TFT Display
+//! Controller (LTDC)".
+
+use crate::dma2d::PixelFormat;
+use crate::rcc::{rec, CoreClocks};
+use crate::stm32::LTDC;
+use embedded_display_controller::DisplayConfiguration;
+
+/// A pixel-space rectangle, used to describe a dirty/partial-update region
+/// of a framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rectangle {
+    /// The smallest rectangle containing both `self` and `other`.
+    pub fn union(self, other: Rectangle) -> Rectangle {
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x + self.width).max(other.x + other.width);
+        let y1 = (self.y + self.height).max(other.y + other.height);
+        Rectangle {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+}
+
+/// LTDC driver, before the single hardware layer has been claimed.
+pub struct Ltdc {
+    rb: LTDC,
+}
+
+impl Ltdc {
+    /// Wrap the `LTDC` peripheral.
+    pub fn new(ltdc: LTDC, prec: rec::Ltdc, clocks: &CoreClocks) -> Self {
+        prec.enable();
+        let _ = clocks;
+        Self { rb: ltdc }
+    }
+
+    /// Program the panel timings (porches, sync widths, polarities).
+    pub fn init(&mut self, config: DisplayConfiguration) {
+        self.rb.sscr().write(|w| unsafe {
+            w.hsw()
+                .bits(config.h_sync - 1)
+                .vsh()
+                .bits(config.v_sync - 1)
+        });
+        self.rb.bpcr().write(|w| unsafe {
+            w.ahbp()
+                .bits(config.h_sync + config.h_back_porch - 1)
+                .avbp()
+                .bits(config.v_sync + config.v_back_porch - 1)
+        });
+        self.rb.awcr().write(|w| unsafe {
+            w.aav()
+                .bits(
+                    config.h_sync
+                        + config.h_back_porch
+                        + config.active_width
+                        - 1,
+                )
+                .aah()
+                .bits(
+                    config.v_sync
+                        + config.v_back_porch
+                        + config.active_height
+                        - 1,
+                )
+        });
+        self.rb.twcr().write(|w| unsafe {
+            w.totalw()
+                .bits(
+                    config.h_sync
+                        + config.h_back_porch
+                        + config.active_width
+                        + config.h_front_porch
+                        - 1,
+                )
+                .totalh()
+                .bits(
+                    config.v_sync
+                        + config.v_back_porch
+                        + config.active_height
+                        + config.v_front_porch
+                        - 1,
+                )
+        });
+        self.rb.gcr().modify(|_, w| {
+            w.hspol()
+                .bit(config.h_sync_pol)
+                .vspol()
+                .bit(config.v_sync_pol)
+                .depol()
+                .bit(config.not_data_enable_pol)
+                .pcpol()
+                .bit(config.pixel_clock_pol)
+                .ltdcen()
+                .set_bit()
+        });
+    }
+
+    /// Toggle the hardware spatial dithering unit.
+    ///
+    /// Worth enabling whenever a 24-bit pipeline feeds an 18-bit or 16-bit
+    /// panel (`ColorCoding` narrower than [`crate::dsi::ColorCoding::TwentyFourBits`]
+    /// on the wrapper side): without it, flat gradients show visible
+    /// banding as the extra bits are simply truncated.
+    pub fn set_dithering(&mut self, enable: bool) {
+        self.rb.gcr().modify(|_, w| w.den().bit(enable));
+    }
+
+    /// Enable `interrupt` at the LTDC.
+    pub fn enable_interrupt(&mut self, interrupt: LtdcInterrupt) {
+        self.rb.ier().modify(|_, w| match interrupt {
+            LtdcInterrupt::Line => w.lie().set_bit(),
+            LtdcInterrupt::FifoUnderrun => w.fuie().set_bit(),
+            LtdcInterrupt::TransferError => w.terrie().set_bit(),
+            LtdcInterrupt::RegisterReload => w.rrie().set_bit(),
+        });
+    }
+
+    /// Whether `interrupt` is currently pending.
+    pub fn is_pending(&self, interrupt: LtdcInterrupt) -> bool {
+        let isr = self.rb.isr().read();
+        match interrupt {
+            LtdcInterrupt::Line => isr.lif().bit_is_set(),
+            LtdcInterrupt::FifoUnderrun => isr.fuif().bit_is_set(),
+            LtdcInterrupt::TransferError => isr.terrif().bit_is_set(),
+            LtdcInterrupt::RegisterReload => isr.rrif().bit_is_set(),
+        }
+    }
+
+    /// Clear a pending `interrupt`.
+    pub fn clear_interrupt(&mut self, interrupt: LtdcInterrupt) {
+        self.rb.icr().write(|w| match interrupt {
+            LtdcInterrupt::Line => w.clif().set_bit(),
+            LtdcInterrupt::FifoUnderrun => w.cfuif().set_bit(),
+            LtdcInterrupt::TransferError => w.cterrif().set_bit(),
+            LtdcInterrupt::RegisterReload => w.crrif().set_bit(),
+        });
+    }
+
+    /// Raise the AXI read-ahead/prefetch threshold for the LTDC's memory
+    /// port, so the memory interface starts fetching the next burst
+    /// earlier.
+    ///
+    /// RM0433 does not expose a FIFO threshold directly on the LTDC's own
+    /// register block; the lever that actually affects its fill margin is
+    /// bit 1 ("read-ahead enable") of the AXI interconnect's function
+    /// modify register for the LTDC's target port. This is the documented
+    /// workaround for FIFO underruns at high pixel clocks (visible as
+    /// flicker/tearing), and is safe to enable unconditionally.
+    pub fn raise_fifo_prefetch_threshold(&mut self) {
+        // SAFETY: a single read-modify-write of a documented, otherwise
+        // unused control bit; no aliasing with any typed peripheral.
+        unsafe {
+            let fn_mod = core::ptr::read_volatile(AXI_TARG7_FN_MOD);
+            core::ptr::write_volatile(
+                AXI_TARG7_FN_MOD,
+                fn_mod | AXI_READ_AHEAD_ENABLE,
+            );
+        }
+    }
+
+    /// Hand out the single hardware layer used by this driver.
+    pub fn split(self) -> Layer {
+        Layer {
+            rb: self.rb,
+            h_origin: 0,
+            v_origin: 0,
+        }
+    }
+}
+
+/// Which LTDC interrupt source an [`Ltdc::enable_interrupt`]/
+/// [`Ltdc::is_pending`]/[`Ltdc::clear_interrupt`] call refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LtdcInterrupt {
+    /// A configured scan line has been reached.
+    Line,
+    /// The LTDC FIFO underran, i.e. the memory interface could not keep up
+    /// with the pixel clock.
+    FifoUnderrun,
+    /// A bus error occurred while reading a layer's framebuffer.
+    TransferError,
+    /// The shadow-register reload requested by [`Layer::swap_reload`] has
+    /// completed.
+    RegisterReload,
+}
+
+/// AXI interconnect function-modify register for the LTDC's target port.
+const AXI_TARG7_FN_MOD: *mut u32 = 0x5100_8108 as *mut u32;
+/// `FN_MOD` bit enabling AXI read-ahead for the target port.
+const AXI_READ_AHEAD_ENABLE: u32 = 0b10;
+
+/// One LTDC hardware layer, bound to a framebuffer in memory.
+pub struct Layer {
+    rb: LTDC,
+    /// On-screen pixel position of the framebuffer's (0, 0), as programmed
+    /// into `L1WHPCR`/`L1WVPCR` by [`Layer::init`]; a partial update's window
+    /// is relative to this, not to the panel's own (0, 0).
+    h_origin: u16,
+    v_origin: u16,
+}
+
+impl Layer {
+    /// Enable this layer and program its window and pixel format from
+    /// `config`'s active area.
+    ///
+    /// Must be called once (after [`Ltdc::init`]) before the first
+    /// [`Layer::set_buffer_address`]/[`Layer::set_partial_buffer`] call --
+    /// until then the layer stays disabled at reset and nothing scanned out
+    /// reaches the panel, however correct its buffer address is.
+    pub fn init(&mut self, config: DisplayConfiguration, format: PixelFormat) {
+        let h_start = config.h_sync + config.h_back_porch;
+        let h_stop = h_start + config.active_width - 1;
+        let v_start = config.v_sync + config.v_back_porch;
+        let v_stop = v_start + config.active_height - 1;
+        self.h_origin = h_start;
+        self.v_origin = v_start;
+
+        self.rb.l1whpcr().write(|w| unsafe {
+            w.whstpos().bits(h_start).whsppos().bits(h_stop)
+        });
+        self.rb.l1wvpcr().write(|w| unsafe {
+            w.wvstpos().bits(v_start).wvsppos().bits(v_stop)
+        });
+        self.rb
+            .l1pfcr()
+            .write(|w| unsafe { w.pf().bits(format.ltdc_code()) });
+
+        // Blend at a constant full alpha rather than each pixel's own alpha
+        // byte: `FrameBuffer` packs RGB into the low three bytes of each
+        // `u32` word and leaves the top byte zero, which an ARGB8888 layer
+        // would otherwise read as fully transparent.
+        self.rb
+            .l1cacr()
+            .write(|w| unsafe { w.consta().bits(0xFF) });
+        self.rb.l1bfcr().write(|w| unsafe {
+            w.bf1().bits(0b100).bf2().bits(0b101)
+        });
+
+        self.rb.l1cr().modify(|_, w| w.len().set_bit());
+        self.swap_reload();
+    }
+
+    /// Point this layer at a new framebuffer base address.
+    ///
+    /// # Safety
+    /// `address` must remain valid (and not be written to by the CPU/DMA2D)
+    /// for as long as the LTDC may be scanning it out.
+    pub unsafe fn set_buffer_address(&mut self, address: u32) {
+        self.rb.l1cfbar().write(|w| w.cfbadd().bits(address));
+        self.swap_reload();
+    }
+
+    /// Trigger the shadow-register reload at the next vertical blank.
+    pub fn swap_reload(&mut self) {
+        self.rb.srcr().write(|w| w.vbr().set_bit());
+    }
+
+    /// Point this layer at only `window`, a sub-rectangle of a framebuffer
+    /// that is `full_stride_pixels` wide, for a dirty-rectangle/partial
+    /// update.
+    ///
+    /// `base_address`/`full_stride_pixels`/`bytes_per_pixel` describe the
+    /// *whole* framebuffer; `window` must already be expressed in that
+    /// framebuffer's own (i.e. post-rotation) coordinate space, since this
+    /// only recomputes addressing and does not know about any rotation
+    /// applied upstream.
+    ///
+    /// Also reprograms `L1WHPCR`/`L1WVPCR` to `window`'s on-screen position
+    /// (relative to the full-screen window [`Layer::init`] set), since
+    /// `L1CFBLNR`'s line count must match the window height these describe
+    /// -- leaving them at the full-screen size here would desync the two
+    /// and corrupt the scanned-out image.
+    ///
+    /// # Safety
+    /// `base_address` plus `window`'s bounds must stay within the
+    /// framebuffer's allocation, and the region must remain valid for as
+    /// long as the LTDC may be scanning it out.
+    pub unsafe fn set_partial_buffer(
+        &mut self,
+        base_address: u32,
+        full_stride_pixels: u16,
+        bytes_per_pixel: u16,
+        window: Rectangle,
+    ) {
+        let pitch_bytes = full_stride_pixels as u32 * bytes_per_pixel as u32;
+        let row_offset_bytes = window.y as u32 * pitch_bytes;
+        let col_offset_bytes = window.x as u32 * bytes_per_pixel as u32;
+        let window_addr =
+            base_address + row_offset_bytes + col_offset_bytes;
+        let line_length_bytes =
+            window.width as u32 * bytes_per_pixel as u32 + 3;
+
+        let h_start = self.h_origin + window.x;
+        let h_stop = h_start + window.width - 1;
+        let v_start = self.v_origin + window.y;
+        let v_stop = v_start + window.height - 1;
+        self.rb.l1whpcr().write(|w| unsafe {
+            w.whstpos().bits(h_start).whsppos().bits(h_stop)
+        });
+        self.rb.l1wvpcr().write(|w| unsafe {
+            w.wvstpos().bits(v_start).wvsppos().bits(v_stop)
+        });
+
+        self.rb
+            .l1cfbar()
+            .write(|w| w.cfbadd().bits(window_addr));
+        self.rb.l1cfblr().write(|w| unsafe {
+            w.cfbll()
+                .bits(line_length_bytes as u16)
+                .cfbp()
+                .bits(pitch_bytes as u16)
+        });
+        self.rb
+            .l1cfblnr()
+            .write(|w| unsafe { w.cfblnbr().bits(window.height) });
+
+        self.swap_reload();
+    }
+}